@@ -1,11 +1,47 @@
 use anyhow::{Context, Result};
 use chef::{Manifest, Recipe};
+use serde::Serialize;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs,
     path::Path,
 };
-use toml_edit::{Document, Item};
+use toml_edit::{Document, Item, Table};
+
+/// Placeholder `package.version` written by [`mask_workspace_versions`].
+const MASKED_VERSION: &str = "0.0.0";
+
+/// Options controlling how a recipe is reduced.
+#[derive(Debug, Clone, Copy)]
+pub struct ReduceOptions {
+    /// Rewrite workspace-local crate versions to a placeholder (see
+    /// [`mask_workspace_versions`]) so bumping a member's version doesn't
+    /// bust the Docker layer cache.
+    pub mask_versions: bool,
+    /// Include `[dev-dependencies]` edges when walking the workspace
+    /// dependency graph. Disable for a production image that doesn't need
+    /// dev-only code paths.
+    pub include_dev_dependencies: bool,
+    /// Include `[build-dependencies]` edges when walking the workspace
+    /// dependency graph. Most images still need these to build, so this
+    /// defaults to `true`; disable it only if the target is known to need
+    /// no build-script-only workspace members.
+    pub include_build_dependencies: bool,
+    /// Seed the root workspace members from `[workspace].default-members`
+    /// instead of `[workspace].members`, if the root manifest declares one.
+    pub use_default_members: bool,
+}
+
+impl Default for ReduceOptions {
+    fn default() -> Self {
+        Self {
+            mask_versions: false,
+            include_dev_dependencies: true,
+            include_build_dependencies: true,
+            use_default_members: false,
+        }
+    }
+}
 
 /// Loads a recipe, reduces it with [`reduce_workspace_recipe`] and
 /// saves the reduces recipe to a file.
@@ -18,11 +54,16 @@ use toml_edit::{Document, Item};
 /// - Could not build dependencies
 /// - Could not filter manifest
 /// - Could not filter lockfile
+/// - Could not mask versions
 /// - Could not save the file
-pub fn reduce_workspace_recipe_file<P: AsRef<Path>>(input_path: &P, output_path: &P) -> Result<()> {
+pub fn reduce_workspace_recipe_file<P: AsRef<Path>>(
+    input_path: &P,
+    output_path: &P,
+    options: ReduceOptions,
+) -> Result<()> {
     let recipe = load_recipe(input_path)?;
 
-    let reduced = reduce_workspace_recipe(&recipe)?;
+    let reduced = reduce_workspace_recipe(&recipe, options)?;
 
     let out = serde_json::to_string(&reduced).context("failed to serialize reduced recipe")?;
     save_recipe(&out, output_path)
@@ -33,6 +74,8 @@ pub fn reduce_workspace_recipe_file<P: AsRef<Path>>(input_path: &P, output_path:
 /// - Finds the root workspace members that the recipe should be reduced to
 /// - Calculates dependencies and transitive dependencies of the root members
 /// - Filters manifest and lockfile
+/// - If `options.mask_versions` is set, rewrites retained workspace members
+///   to a stable placeholder version (see [`mask_workspace_versions`])
 ///
 /// # Errors
 /// - Could not get root manifest
@@ -41,23 +84,240 @@ pub fn reduce_workspace_recipe_file<P: AsRef<Path>>(input_path: &P, output_path:
 /// - Could not build dependencies
 /// - Could not filter manifest
 /// - Could not filter lockfile
-pub fn reduce_workspace_recipe(recipe: &Recipe) -> Result<Recipe> {
+/// - Could not mask versions
+pub fn reduce_workspace_recipe(recipe: &Recipe, options: ReduceOptions) -> Result<Recipe> {
+    let root_manifest = get_root_manifest(recipe)?;
+
+    let root_members =
+        get_root_workspace_members(recipe, root_manifest, options.use_default_members)?;
+
+    reduce_from_roots(recipe, &root_members, options)
+}
+
+/// Reduce a recipe down to a single binary target and its transitive
+/// dependencies, instead of every root workspace member.
+///
+/// This is useful for a multi-binary workspace where only one service is
+/// being containerized: the produced recipe only contains what that binary
+/// needs to build, not the whole workspace.
+///
+/// # Errors
+/// - No workspace member declares a binary named `target_name`
+/// - Could not find all workspace members
+/// - Could not build dependencies
+/// - Could not filter manifest
+/// - Could not filter lockfile
+/// - Could not mask versions
+pub fn reduce_to_target(
+    recipe: &Recipe,
+    target_name: &str,
+    options: ReduceOptions,
+) -> Result<Recipe> {
+    let root_member = find_member_with_binary(recipe, target_name)
+        .with_context(|| format!("no workspace member declares binary `{target_name}`"))?;
+
+    reduce_from_roots(recipe, &HashSet::from([root_member]), options)
+}
+
+/// Outcome of reducing a single workspace member: either it was kept
+/// because it's a root or reachable from one, or it was dropped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MemberOutcome {
+    /// Kept. `path` is the root-to-member chain that pulled it in, empty if
+    /// this member is itself a root.
+    Kept { path: Vec<String> },
+    /// Not reachable from any root workspace member.
+    Dropped,
+}
+
+/// Report describing how a recipe was reduced: which members were kept or
+/// dropped and why, the resolved dependency edges used to decide, and any
+/// dependency cycles detected while walking them.
+///
+/// Returned by [`reduce_workspace_recipe_with_report`] and
+/// [`reduce_to_target_with_report`]. Implements [`Display`](std::fmt::Display)
+/// for a human-readable summary, and [`Serialize`] for CI consumption as
+/// JSON via [`ReductionReport::to_json`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ReductionReport {
+    /// Outcome for every workspace member the recipe originally had.
+    pub members: BTreeMap<String, MemberOutcome>,
+    /// The resolved dependency edges of every workspace member, as passed to
+    /// [`compute_transitive_members_with_report`].
+    pub edges: BTreeMap<String, BTreeSet<String>>,
+    /// Dependency cycles detected while traversing the graph, each given as
+    /// the member chain that closes the loop, e.g. `["a", "b", "a"]`.
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl ReductionReport {
+    /// Serialize the report as JSON, for CI consumption.
+    ///
+    /// # Errors
+    /// - Could not serialize the report
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize reduction report")
+    }
+}
+
+impl std::fmt::Display for ReductionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "kept:")?;
+        for (name, outcome) in &self.members {
+            match outcome {
+                MemberOutcome::Kept { path } if path.is_empty() => writeln!(f, "  {name} (root)")?,
+                MemberOutcome::Kept { path } => {
+                    writeln!(f, "  {name} (via {})", path.join(" -> "))?;
+                }
+                MemberOutcome::Dropped => {}
+            }
+        }
+
+        writeln!(f, "dropped:")?;
+        for (name, outcome) in &self.members {
+            if matches!(outcome, MemberOutcome::Dropped) {
+                writeln!(f, "  {name} (not referenced by any root member)")?;
+            }
+        }
+
+        if !self.cycles.is_empty() {
+            writeln!(f, "cycles:")?;
+            for cycle in &self.cycles {
+                writeln!(f, "  {}", cycle.join(" -> "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reduce a workspace recipe like [`reduce_workspace_recipe`], additionally
+/// returning a [`ReductionReport`] of which members were kept or dropped,
+/// the resolved dependency edges, and any dependency cycles found.
+///
+/// # Errors
+/// Same as [`reduce_workspace_recipe`].
+pub fn reduce_workspace_recipe_with_report(
+    recipe: &Recipe,
+    options: ReduceOptions,
+) -> Result<(Recipe, ReductionReport)> {
     let root_manifest = get_root_manifest(recipe)?;
 
-    let root_members = get_root_workspace_members(root_manifest)?;
+    let root_members =
+        get_root_workspace_members(recipe, root_manifest, options.use_default_members)?;
 
+    reduce_from_roots_with_report(recipe, &root_members, options)
+}
+
+/// Reduce a recipe to a single binary target like [`reduce_to_target`],
+/// additionally returning a [`ReductionReport`].
+///
+/// # Errors
+/// Same as [`reduce_to_target`].
+pub fn reduce_to_target_with_report(
+    recipe: &Recipe,
+    target_name: &str,
+    options: ReduceOptions,
+) -> Result<(Recipe, ReductionReport)> {
+    let root_member = find_member_with_binary(recipe, target_name)
+        .with_context(|| format!("no workspace member declares binary `{target_name}`"))?;
+
+    reduce_from_roots_with_report(recipe, &HashSet::from([root_member]), options)
+}
+
+/// Shared reduction pipeline once the root workspace members are known:
+/// calculates dependencies and transitive dependencies of the root members,
+/// then filters manifest and lockfile down to what's kept.
+fn reduce_from_roots(
+    recipe: &Recipe,
+    root_members: &HashSet<String>,
+    options: ReduceOptions,
+) -> Result<Recipe> {
+    reduce_from_roots_with_report(recipe, root_members, options).map(|(reduced, _)| reduced)
+}
+
+/// Same pipeline as [`reduce_from_roots`], additionally building a
+/// [`ReductionReport`] from the traversal.
+fn reduce_from_roots_with_report(
+    recipe: &Recipe,
+    root_members: &HashSet<String>,
+    options: ReduceOptions,
+) -> Result<(Recipe, ReductionReport)> {
     let all_members = get_all_workspace_members(recipe);
 
-    let dependencies = build_workspace_deps(recipe, &all_members);
+    let dependencies =
+        build_workspace_deps(
+            recipe,
+            &all_members,
+            options.include_dev_dependencies,
+            options.include_build_dependencies,
+        )?;
 
-    let keep_members = compute_transitive_members(&root_members, &dependencies);
+    let (keep_members, traversal) =
+        compute_transitive_members_with_report(root_members, &dependencies);
+
+    let members = all_members
+        .iter()
+        .map(|name| {
+            let outcome = if keep_members.contains(name) {
+                MemberOutcome::Kept {
+                    path: traversal.paths.get(name).cloned().unwrap_or_default(),
+                }
+            } else {
+                MemberOutcome::Dropped
+            };
+            (name.clone(), outcome)
+        })
+        .collect();
+
+    let edges = dependencies
+        .iter()
+        .map(|(name, deps)| (name.clone(), deps.iter().cloned().collect()))
+        .collect();
+
+    let report = ReductionReport {
+        members,
+        edges,
+        cycles: traversal.cycles,
+    };
 
     let mut reduced = recipe.clone();
     filter_manifests(&mut reduced, &keep_members);
 
     filter_lockfile_members(&mut reduced, &all_members, &keep_members)?;
 
-    Ok(reduced)
+    if options.mask_versions {
+        mask_workspace_versions(&mut reduced, &keep_members)?;
+    }
+
+    Ok((reduced, report))
+}
+
+/// Find the workspace member that declares a binary target named
+/// `target_name`, either explicitly via `[[bin]] name = "..."` or, failing
+/// that, via cargo's `src/main.rs` autodiscovery.
+///
+/// The skeleton only carries manifest contents and the lockfile, not the
+/// member's source tree, so there's no way to check whether `src/main.rs`
+/// actually exists. As an approximation, a member is treated as having an
+/// implicit binary of its own crate name unless it explicitly declares a
+/// `[lib]` table, since a crate that configures `[lib]` is unlikely to also
+/// be the one providing the binary of the same name. This can still
+/// misidentify a library-only crate that has neither `[lib]` nor
+/// `src/main.rs`; that case can't be distinguished without the source tree.
+fn find_member_with_binary(recipe: &Recipe, target_name: &str) -> Option<String> {
+    recipe.skeleton.manifests.iter().find_map(|manifest| {
+        let name = extract_crate_name(manifest)?;
+        let doc: Document<String> = manifest.contents.parse().ok()?;
+
+        let declares_bin = matches!(doc.get("bin"), Some(Item::ArrayOfTables(bins))
+            if bins.iter().any(|bin| bin.get("name").and_then(|v| v.as_str()) == Some(target_name)));
+
+        let looks_like_implicit_bin = name == target_name && doc.get("lib").is_none();
+
+        (declares_bin || looks_like_implicit_bin).then_some(name)
+    })
 }
 
 /// Find root Cargo.toml
@@ -70,21 +330,108 @@ fn get_root_manifest(recipe: &Recipe) -> Result<&Manifest> {
         .context("no root Cargo.toml found")
 }
 
-/// Extract the root workspace members that the recipe will be reduce to
-fn get_root_workspace_members(root: &Manifest) -> Result<HashSet<String>> {
+/// Extract the root workspace members that the recipe will be reduced to.
+///
+/// Entries are glob patterns matched against every manifest's directory in
+/// the skeleton (a literal entry like `"bar"` is just a pattern with no
+/// wildcard), resolved back to a crate name via [`extract_crate_name`].
+/// Directories matching `[workspace].exclude` are subtracted. If
+/// `use_default_members` is set and the root manifest declares
+/// `[workspace].default-members`, that list seeds the root set instead of
+/// `[workspace].members`.
+fn get_root_workspace_members(
+    recipe: &Recipe,
+    root: &Manifest,
+    use_default_members: bool,
+) -> Result<HashSet<String>> {
     let doc: Document<String> = root
         .contents
         .parse()
         .context("root Cargo.toml is not valid toml")?;
 
-    let members = doc["workspace"]["members"]
-        .as_array()
-        .context("[workspace].members must be an array")?;
+    let workspace = doc["workspace"]
+        .as_table()
+        .context("[workspace] must be a table")?;
+
+    let seed_key = if use_default_members && workspace.contains_key("default-members") {
+        "default-members"
+    } else {
+        "members"
+    };
+
+    let patterns = workspace
+        .get(seed_key)
+        .and_then(Item::as_array)
+        .with_context(|| format!("[workspace].{seed_key} must be an array"))?;
+
+    let excludes: Vec<&str> = workspace
+        .get("exclude")
+        .and_then(Item::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .collect();
+
+    let mut members = HashSet::new();
+    for pattern in patterns.iter().filter_map(|v| v.as_str()) {
+        for manifest in &recipe.skeleton.manifests {
+            let Some(dir) = member_dir(manifest) else {
+                continue;
+            };
 
-    Ok(members
-        .iter()
-        .filter_map(|x| x.as_str().map(ToString::to_string))
-        .collect())
+            if !glob_match(pattern, dir) || excludes.iter().any(|ex| glob_match(ex, dir)) {
+                continue;
+            }
+
+            if let Some(name) = extract_crate_name(manifest) {
+                members.insert(name);
+            }
+        }
+    }
+
+    Ok(members)
+}
+
+/// The workspace-relative directory a manifest lives in, e.g. `"crates/foo"`
+/// for `crates/foo/Cargo.toml`, or `""` for the root manifest.
+fn member_dir(manifest: &Manifest) -> Option<&str> {
+    manifest.relative_path.parent().and_then(Path::to_str)
+}
+
+/// Minimal glob matcher for `[workspace].members`/`exclude` entries: `*`
+/// matches any run of characters within a path segment, `**` matches any
+/// number of path segments (including none). Only the first `*` in a segment
+/// is a wildcard; `?` and `[...]` character classes are matched literally.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn segment_match(pattern: &str, segment: &str) -> bool {
+        match pattern.split_once('*') {
+            None => pattern == segment,
+            Some((prefix, suffix)) => {
+                segment.len() >= prefix.len() + suffix.len()
+                    && segment.starts_with(prefix)
+                    && segment.ends_with(suffix)
+            }
+        }
+    }
+
+    fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+        match pattern.first() {
+            None => path.is_empty(),
+            Some(&"**") => {
+                match_segments(&pattern[1..], path)
+                    || (!path.is_empty() && match_segments(pattern, &path[1..]))
+            }
+            Some(p) => {
+                !path.is_empty()
+                    && segment_match(p, path[0])
+                    && match_segments(&pattern[1..], &path[1..])
+            }
+        }
+    }
+
+    let pattern_segments: Vec<&str> = pattern.split('/').collect();
+    let path_segments: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern_segments, &path_segments)
 }
 
 // Extract all workspace members
@@ -94,10 +441,25 @@ fn get_all_workspace_members(recipe: &Recipe) -> HashSet<String> {
 }
 
 /// Build workspace dependency map
+///
+/// Scans `[dependencies]`, `[target.'cfg(...)'.*]` variants, and (when the
+/// corresponding `include_*` flag is set) `[build-dependencies]` and
+/// `[dev-dependencies]`. Renamed/path deps (`key = { package = "real" }`)
+/// and deps inherited from `[workspace.dependencies]`
+/// (`key = { workspace = true }`) are resolved to their real crate name
+/// before checking membership.
+///
+/// # Errors
+/// - Could not get root manifest
+/// - Root manifest is not valid toml
 fn build_workspace_deps(
     recipe: &Recipe,
     all_members: &HashSet<String>,
-) -> HashMap<String, HashSet<String>> {
+    include_dev_dependencies: bool,
+    include_build_dependencies: bool,
+) -> Result<HashMap<String, HashSet<String>>> {
+    let workspace_deps = workspace_dependencies_table(get_root_manifest(recipe)?)?;
+
     let mut map = HashMap::new();
 
     for manifest in &recipe.skeleton.manifests {
@@ -107,37 +469,239 @@ fn build_workspace_deps(
                 Ok(d) => d,
                 Err(_) => continue,
             };
-            if let Some(table) = doc.get("dependencies").and_then(|v| v.as_table()) {
-                for (dep_name, _) in table {
-                    if all_members.contains(dep_name) {
-                        deps.insert(dep_name.to_string());
+
+            for table in dependency_tables(&doc, include_dev_dependencies, include_build_dependencies) {
+                for (dep_key, dep_item) in table {
+                    let dep_name = resolve_dep_name(dep_key, dep_item, &workspace_deps);
+                    if all_members.contains(&dep_name) {
+                        deps.insert(dep_name);
                     }
                 }
             }
+
             map.insert(name, deps);
         }
     }
 
-    map
+    Ok(map)
+}
+
+/// The `[workspace.dependencies]` table of the root manifest, used to
+/// resolve `key = { workspace = true }` entries. Empty if the root manifest
+/// declares no such table.
+fn workspace_dependencies_table(root: &Manifest) -> Result<Table> {
+    let doc: Document<String> = root
+        .contents
+        .parse()
+        .context("root Cargo.toml is not valid toml")?;
+
+    Ok(doc
+        .get("workspace")
+        .and_then(Item::as_table)
+        .and_then(|t| t.get("dependencies"))
+        .and_then(Item::as_table)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Collect every dependency table of a manifest that can carry workspace
+/// member edges: `[dependencies]`, optionally `[build-dependencies]` and
+/// `[dev-dependencies]`, and their `[target.'cfg(...)'.*]` counterparts.
+fn dependency_tables<'a>(
+    doc: &'a Document<String>,
+    include_dev_dependencies: bool,
+    include_build_dependencies: bool,
+) -> Vec<&'a Table> {
+    let mut tables = Vec::new();
+    push_dep_tables(
+        &mut tables,
+        doc.as_table(),
+        include_dev_dependencies,
+        include_build_dependencies,
+    );
+
+    if let Some(target) = doc.get("target").and_then(Item::as_table) {
+        for (_, cfg_item) in target {
+            if let Some(cfg_table) = cfg_item.as_table() {
+                push_dep_tables(
+                    &mut tables,
+                    cfg_table,
+                    include_dev_dependencies,
+                    include_build_dependencies,
+                );
+            }
+        }
+    }
+
+    tables
 }
 
-/// Compute transitive dependencies of workspace members
-fn compute_transitive_members(
+/// Push the `dependencies`/`build-dependencies`/`dev-dependencies` child
+/// tables of `container` onto `tables`, if present and enabled.
+fn push_dep_tables<'a>(
+    tables: &mut Vec<&'a Table>,
+    container: &'a Table,
+    include_dev_dependencies: bool,
+    include_build_dependencies: bool,
+) {
+    if let Some(table) = container.get("dependencies").and_then(Item::as_table) {
+        tables.push(table);
+    }
+    if include_build_dependencies
+        && let Some(table) = container.get("build-dependencies").and_then(Item::as_table)
+    {
+        tables.push(table);
+    }
+    if include_dev_dependencies
+        && let Some(table) = container.get("dev-dependencies").and_then(Item::as_table)
+    {
+        tables.push(table);
+    }
+}
+
+/// Resolve the real crate name behind a dependency table entry, accounting
+/// for renamed/path deps (`key = { package = "real-name" }`) and deps
+/// inherited from `[workspace.dependencies]` (`key = { workspace = true }`).
+///
+/// This does not resolve a path dependency's crate name from its path's
+/// basename (e.g. `key = { path = "../other-name" }`). Cargo requires the
+/// dependency key to equal the package name whenever `package =` is absent,
+/// so the key is already the real crate name in that case; there's no
+/// "basename differs from key" scenario to handle.
+fn resolve_dep_name(key: &str, value: &Item, workspace_deps: &Table) -> String {
+    let Some(inline) = value.as_table_like() else {
+        return key.to_string();
+    };
+
+    if inline.get("workspace").and_then(Item::as_bool) == Some(true) {
+        return workspace_deps
+            .get(key)
+            .and_then(Item::as_table_like)
+            .and_then(|t| t.get("package"))
+            .and_then(Item::as_str)
+            .map_or_else(|| key.to_string(), ToString::to_string);
+    }
+
+    inline
+        .get("package")
+        .and_then(Item::as_str)
+        .map_or_else(|| key.to_string(), ToString::to_string)
+}
+
+/// Record of a [`compute_transitive_members_with_report`] traversal.
+#[derive(Debug, Clone, Default)]
+struct TraversalReport {
+    /// For each kept non-root member, the root-to-member path that first
+    /// pulled it in.
+    paths: HashMap<String, Vec<String>>,
+    /// Dependency cycles found while traversing, each given as the member
+    /// chain that closes the loop, e.g. `["a", "b", "a"]`.
+    cycles: Vec<Vec<String>>,
+}
+
+/// Compute transitive dependencies of workspace members, additionally
+/// recording a [`TraversalReport`]: for each kept member, the path from a
+/// root that pulled it in, and any dependency cycles encountered. A
+/// dependency that is already on the current root-to-here path is reported
+/// as a cycle instead of silently relying on the visited set to swallow the
+/// repeat visit. A graph with a cycle always yields at least one reported
+/// cycle, but not every loop is listed: one that closes through a member
+/// already visited off the current path is not reported.
+fn compute_transitive_members_with_report(
     root_members: &HashSet<String>,
     deps: &HashMap<String, HashSet<String>>,
-) -> HashSet<String> {
+) -> (HashSet<String>, TraversalReport) {
     let mut keep = HashSet::new();
-    let mut stack: Vec<&String> = root_members.iter().collect();
+    let mut report = TraversalReport::default();
+
+    let mut roots: Vec<&String> = root_members.iter().collect();
+    roots.sort();
+
+    for root in roots {
+        let mut path = vec![root.clone()];
+        visit_member(root, deps, &mut keep, &mut path, &mut report);
+    }
+
+    // A root reached as a dependency of an earlier root is still a root.
+    report.paths.retain(|member, _| !root_members.contains(member));
+    report.cycles = dedup_cycles(&report.cycles);
+
+    (keep, report)
+}
+
+/// DFS helper for [`compute_transitive_members_with_report`]. `path` is the
+/// chain of members on the current root-to-here recursion stack, used to
+/// both record how a member was reached and to detect cycles (a dependency
+/// that's already an ancestor on `path`). Dependencies are visited in
+/// sorted order so the recorded path and cycles are deterministic for a
+/// given graph, regardless of the `HashSet` iteration order of `deps`.
+fn visit_member(
+    member: &str,
+    deps: &HashMap<String, HashSet<String>>,
+    keep: &mut HashSet<String>,
+    path: &mut Vec<String>,
+    report: &mut TraversalReport,
+) {
+    if !keep.insert(member.to_string()) {
+        return;
+    }
+    if path.len() > 1 {
+        report.paths.insert(member.to_string(), path.clone());
+    }
+
+    let Some(dependents) = deps.get(member) else {
+        return;
+    };
 
-    while let Some(member) = stack.pop() {
-        if keep.insert(member.clone())
-            && let Some(ds) = deps.get(member)
-        {
-            stack.extend(ds.iter());
+    let mut dependents: Vec<&String> = dependents.iter().collect();
+    dependents.sort();
+
+    for dep in dependents {
+        if let Some(cycle_start) = path.iter().position(|m| m == dep) {
+            let mut cycle = path[cycle_start..].to_vec();
+            cycle.push(dep.clone());
+            report.cycles.push(cycle);
+            continue;
         }
+
+        path.push(dep.clone());
+        visit_member(dep, deps, keep, path, report);
+        path.pop();
     }
+}
 
-    keep
+/// Canonicalize and deduplicate detected cycles so the same cycle reached
+/// from different roots or entry points produces identical output, keeping
+/// [`ReductionReport::to_json`] reproducible for CI diffing.
+fn dedup_cycles(cycles: &[Vec<String>]) -> Vec<Vec<String>> {
+    let mut canonical: Vec<Vec<String>> = cycles.iter().map(|c| canonicalize_cycle(c)).collect();
+    canonical.sort();
+    canonical.dedup();
+    canonical
+}
+
+/// Rotate a cycle chain (e.g. `["a", "b", "a"]`) so it starts at its
+/// lexicographically smallest member, so that the same cycle entered at a
+/// different point compares equal.
+fn canonicalize_cycle(cycle: &[String]) -> Vec<String> {
+    if cycle.len() <= 1 {
+        return cycle.to_vec();
+    }
+
+    let loop_members = &cycle[..cycle.len() - 1];
+    let min_index = loop_members
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, member)| member.as_str())
+        .map_or(0, |(i, _)| i);
+
+    let mut rotated: Vec<String> = loop_members[min_index..]
+        .iter()
+        .chain(&loop_members[..min_index])
+        .cloned()
+        .collect();
+    rotated.push(rotated[0].clone());
+    rotated
 }
 
 /// Filter manifests to keep only the workspace members we want
@@ -176,6 +740,51 @@ fn filter_lockfile_members(
     Ok(())
 }
 
+/// Rewrite `package.version` of every retained workspace member to
+/// [`MASKED_VERSION`], in both the manifests and the lockfile.
+///
+/// This is an opt-in pass for cache-friendly Docker layers: a cargo-chef
+/// layer built from the recipe stays valid across version bumps that don't
+/// change the dependency graph, since the recipe no longer changes when a
+/// kept member's `version` does. Only workspace-local crate versions are
+/// touched; third-party dependency requirements are left untouched.
+fn mask_workspace_versions(recipe: &mut Recipe, keep_members: &HashSet<String>) -> Result<()> {
+    for manifest in &mut recipe.skeleton.manifests {
+        if extract_crate_name(manifest).is_some_and(|name| keep_members.contains(&name)) {
+            let doc: Document<String> = manifest
+                .contents
+                .parse()
+                .context("manifest is not valid toml")?;
+            let mut doc = doc.into_mut();
+
+            doc["package"]["version"] = toml_edit::value(MASKED_VERSION);
+
+            manifest.contents = doc.to_string();
+        }
+    }
+
+    if let Some(lock_txt) = &recipe.skeleton.lock_file {
+        let doc: Document<String> = lock_txt.parse()?;
+        let mut doc = doc.into_mut();
+
+        if let Some(Item::ArrayOfTables(array)) = doc.get_mut("package") {
+            for pkg in array.iter_mut() {
+                let is_member = pkg
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|name| keep_members.contains(name));
+                if is_member {
+                    pkg.insert("version", toml_edit::value(MASKED_VERSION));
+                }
+            }
+        }
+
+        recipe.skeleton.lock_file = Some(doc.to_string());
+    }
+
+    Ok(())
+}
+
 /// Extract the crate name from a manifest
 fn extract_crate_name(manifest: &Manifest) -> Option<String> {
     let doc: Document<String> = manifest.contents.parse().ok()?;
@@ -210,7 +819,7 @@ mod tests {
         let want_path = "test-files/recipe.json";
 
         let recipe = load_recipe(given_path)?;
-        let reduced = reduce_workspace_recipe(&recipe)?;
+        let reduced = reduce_workspace_recipe(&recipe, ReduceOptions::default())?;
 
         let want_reduced = load_recipe(want_path)?;
 
@@ -227,7 +836,7 @@ mod tests {
         let want_path = "test-files/want-recipe-bar.json";
 
         let recipe = load_recipe(given_path)?;
-        let reduced = reduce_workspace_recipe(&recipe)?;
+        let reduced = reduce_workspace_recipe(&recipe, ReduceOptions::default())?;
 
         let want_reduced = load_recipe(want_path)?;
 
@@ -244,7 +853,7 @@ mod tests {
         let want_path = "test-files/want-recipe-baz.json";
 
         let recipe = load_recipe(given_path)?;
-        let reduced = reduce_workspace_recipe(&recipe)?;
+        let reduced = reduce_workspace_recipe(&recipe, ReduceOptions::default())?;
 
         let want_reduced = load_recipe(want_path)?;
 
@@ -254,4 +863,148 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_mask_versions_rewrites_member_versions() -> Result<()> {
+        let given_path = "test-files/given-recipe-baz.json";
+        let want_path = "test-files/want-recipe-baz-masked.json";
+
+        let recipe = load_recipe(given_path)?;
+        let reduced = reduce_workspace_recipe(
+            &recipe,
+            ReduceOptions {
+                mask_versions: true,
+                ..ReduceOptions::default()
+            },
+        )?;
+
+        let want_reduced = load_recipe(want_path)?;
+
+        assert_eq!(
+            reduced, want_reduced,
+            "masked recipe does not match expected output"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduce_to_target_keeps_only_the_binary_crate() -> Result<()> {
+        let given_path = "test-files/given-recipe-baz.json";
+        let want_path = "test-files/want-recipe-baz-target-bin.json";
+
+        let recipe = load_recipe(given_path)?;
+        let reduced = reduce_to_target(&recipe, "bin", ReduceOptions::default())?;
+
+        let want_reduced = load_recipe(want_path)?;
+
+        assert_eq!(
+            reduced, want_reduced,
+            "reduced recipe does not match expected output"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduce_recipe_excludes_dev_only_members_when_disabled() -> Result<()> {
+        let given_path = "test-files/given-recipe-dev-dep.json";
+        let want_path = "test-files/want-recipe-dev-dep-excluded.json";
+
+        let recipe = load_recipe(given_path)?;
+        let reduced = reduce_workspace_recipe(
+            &recipe,
+            ReduceOptions {
+                include_dev_dependencies: false,
+                ..ReduceOptions::default()
+            },
+        )?;
+
+        let want_reduced = load_recipe(want_path)?;
+
+        assert_eq!(
+            reduced, want_reduced,
+            "reduced recipe does not match expected output"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduce_recipe_with_glob_members() -> Result<()> {
+        let given_path = "test-files/given-recipe-glob-members.json";
+        let want_path = "test-files/want-recipe-glob-members.json";
+
+        let recipe = load_recipe(given_path)?;
+        let reduced = reduce_workspace_recipe(&recipe, ReduceOptions::default())?;
+
+        let want_reduced = load_recipe(want_path)?;
+
+        assert_eq!(
+            reduced, want_reduced,
+            "reduced recipe does not match expected output"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("crates/*", "crates/foo"));
+        assert!(!glob_match("crates/*", "crates/foo/bar"));
+        assert!(glob_match("crates/**", "crates/foo/bar"));
+        assert!(glob_match("crates/**", "crates"));
+        assert!(glob_match("bar", "bar"));
+        assert!(!glob_match("bar", "baz"));
+    }
+
+    #[test]
+    fn test_compute_transitive_members_with_report_detects_cycle() {
+        let deps = HashMap::from([
+            ("a".to_string(), HashSet::from(["b".to_string()])),
+            ("b".to_string(), HashSet::from(["a".to_string()])),
+        ]);
+        let roots = HashSet::from(["a".to_string()]);
+
+        let (keep, report) = compute_transitive_members_with_report(&roots, &deps);
+
+        assert_eq!(keep, HashSet::from(["a".to_string(), "b".to_string()]));
+        assert_eq!(
+            report.paths.get("b"),
+            Some(&vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            report.cycles,
+            vec![vec!["a".to_string(), "b".to_string(), "a".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_dedup_cycles_canonicalizes_rotations_and_dedupes() {
+        let cycles = vec![
+            vec!["b".to_string(), "a".to_string(), "b".to_string()],
+            vec!["a".to_string(), "b".to_string(), "a".to_string()],
+        ];
+
+        assert_eq!(
+            dedup_cycles(&cycles),
+            vec![vec!["a".to_string(), "b".to_string(), "a".to_string()]],
+            "rotated duplicates of the same cycle must collapse to one canonical entry"
+        );
+    }
+
+    #[test]
+    fn test_reduce_workspace_recipe_with_report_marks_dropped_members() -> Result<()> {
+        let recipe = load_recipe("test-files/given-recipe-bar.json")?;
+        let (reduced, report) =
+            reduce_workspace_recipe_with_report(&recipe, ReduceOptions::default())?;
+
+        let want_reduced = load_recipe("test-files/want-recipe-bar.json")?;
+        assert_eq!(reduced, want_reduced);
+
+        assert!(
+            report
+                .members
+                .values()
+                .any(|outcome| matches!(outcome, MemberOutcome::Dropped)),
+            "expected at least one dropped member in the report"
+        );
+        Ok(())
+    }
 }